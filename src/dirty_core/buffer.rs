@@ -2,11 +2,9 @@ use std::ops::{Add, AddAssign, Mul, MulAssign};
 
 use anyhow::{Context, Result};
 
-use super::core::Float;
-
 pub trait Sample: Add + AddAssign + Mul + MulAssign + Sized {}
 
-impl Sample for Float {}
+impl Sample for f32 {}
 
 #[derive(Debug, Clone)]
 pub struct Buffer<T> {
@@ -52,10 +50,29 @@ impl<T: Clone + Copy + Default + Sample + Sync> BuffVec<T> {
         }
     }
 
+    /// Like `new`, but each channel's buffer is pre-sized to `buffer_size`
+    /// zeroed samples instead of starting empty. Needed for accumulators that
+    /// are overdubbed into directly, since `Buffer::_overdub` writes in place
+    /// against the existing length rather than growing it.
+    pub fn new_zeroed(channels: usize, buffer_size: usize) -> Self {
+        Self {
+            data: vec![Buffer::<T>::new(buffer_size); channels],
+            outer_pointer: 0,
+            inner_pointer: 0,
+        }
+    }
+
     pub fn get_buffer(&self, index: usize) -> Result<Vec<T>> {
         self.data.get(index).context("out of bounds read")?.read()
     }
 
+    pub fn overdub_channel(&mut self, index: usize, data: Vec<T>) -> Result<()> {
+        self.data
+            .get_mut(index)
+            .context("out of bounds write")?
+            ._overdub(data)
+    }
+
     fn get_next(&mut self) -> Option<T> {
         match self.data.get(self.outer_pointer) {
             None => {
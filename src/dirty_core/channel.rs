@@ -1,5 +1,9 @@
-use std::sync::Arc;
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
 
+use anyhow::Result;
 use tokio::{
     runtime::Handle,
     sync::{
@@ -12,6 +16,7 @@ use tokio::{
 use super::{
     core::{AudioIO, DirtyCoreMessage, PhysicalAudioIO},
     output_system::OutputSystemMessage,
+    player::LoadedFile,
     BuffVec,
 };
 
@@ -35,8 +40,6 @@ pub enum ChannelMessage {
 
     SetOuptutSystem(Sender<OutputSystemMessage>),
 
-    RegisterMaster(Sender<()>),
-
     NewBuffer(Arc<BuffVec<f32>>),
 }
 
@@ -54,6 +57,8 @@ pub struct Channel {
 
     audio_system: Sender<DirtyCoreMessage>,
     output_system: Option<Sender<OutputSystemMessage>>,
+
+    loaded_file: Option<Arc<Mutex<LoadedFile>>>,
 }
 
 impl Channel {
@@ -73,6 +78,8 @@ impl Channel {
 
             audio_system,
             output_system: None,
+
+            loaded_file: None,
         }
     }
 
@@ -80,6 +87,19 @@ impl Channel {
         self.channel_tx.clone()
     }
 
+    pub(crate) fn set_output_system(&mut self, output_system: Sender<OutputSystemMessage>) {
+        self.output_system = Some(output_system);
+    }
+
+    /// Loads a WAV file and routes this channel's input from it, looping
+    /// once playback reaches the end. See `player::LoadedFile`.
+    pub fn load_file(&mut self, path: PathBuf) -> Result<()> {
+        let loaded = LoadedFile::load(path)?;
+        self.loaded_file = Some(Arc::new(Mutex::new(loaded)));
+        self.input = AudioIO::File;
+        Ok(())
+    }
+
     pub async fn run_channel(mut self) {
         loop {
             let message = self.channel_rx.recv().await.unwrap();
@@ -120,10 +140,6 @@ impl Channel {
                     self.output_system = Some(sender);
                 }
 
-                ChannelMessage::RegisterMaster(_sender) => {
-                    todo!();
-                }
-
                 ChannelMessage::NewBuffer(data) => {
                     self.process_audio(data).await;
                 }
@@ -131,34 +147,56 @@ impl Channel {
         }
     }
 
-    async fn process_audio(&self, data: Arc<BuffVec<f32>>) {
+    pub(crate) async fn process_audio(&self, data: Arc<BuffVec<f32>>) {
         let input = self.input;
         let channel_volume = self.volume;
+        let panning = self.panning;
 
         let audio_system = self.audio_system.clone();
         let output_system = self.output_system.clone();
+        let loaded_file = self.loaded_file.clone();
         let self_address = self.get_channel_tx();
         let _ = spawn_blocking(move || {
-            let mut input_data = match input {
+            let stereo_input = match input {
                 AudioIO::None => None,
-                AudioIO::Hardware(physical_audio_io) => match physical_audio_io {
-                    PhysicalAudioIO::Mono(c) => Some(data.get_buffer(c).unwrap()),
-                    PhysicalAudioIO::Stereo(l, _r) => Some(data.get_buffer(l).unwrap()), // Some(Buffer::<StereoSample>::from_vectors(
-                                                                                         //     data.get_buffer(l).unwrap(),
-                                                                                         //     data.get_buffer(_r).unwrap(),
-                                                                                         // )),
-                },
-            }
-            .unwrap();
-            input_data.iter_mut().for_each(|s| {
-                *s *= channel_volume;
-            });
+                AudioIO::Hardware(PhysicalAudioIO::Mono(c)) => {
+                    data.get_buffer(c).ok().map(|mono| (mono.clone(), mono))
+                }
+                AudioIO::Hardware(PhysicalAudioIO::Stereo(l, r)) => {
+                    data.get_buffer(l).ok().zip(data.get_buffer(r).ok())
+                }
+                AudioIO::File => {
+                    let block_len = data.get_buffer(0).map(|b| b.len()).unwrap_or(0);
+                    loaded_file.map(|file| {
+                        file.lock()
+                            .expect("loaded file lock failed")
+                            .next_block(block_len)
+                    })
+                }
+            };
+            // No source routed to this channel (or an out-of-bounds one); nothing to process.
+            let (mut left, mut right) = match stereo_input {
+                Some(pair) => pair,
+                None => return,
+            };
+
+            // Equal-power pan law: at center both gains are ~0.707 (-3dB), which
+            // keeps left_gain^2 + right_gain^2 constant across the sweep.
+            let theta = (panning + 1.0) * std::f32::consts::PI / 4.0;
+            let left_gain = theta.cos();
+            let right_gain = theta.sin();
+
+            left.iter_mut()
+                .for_each(|s| *s *= channel_volume * left_gain);
+            right
+                .iter_mut()
+                .for_each(|s| *s *= channel_volume * right_gain);
 
             Handle::current().block_on(async {
                 match output_system {
                     None => (),
                     Some(tx) => {
-                        if (tx.send(OutputSystemMessage::Overdub(input_data)).await).is_err() {
+                        if (tx.send(OutputSystemMessage::Overdub(left, right)).await).is_err() {
                             let (sender, reciever) = oneshot::channel();
                             audio_system
                                 .send(DirtyCoreMessage::GetOutputSystem(sender))
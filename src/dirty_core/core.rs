@@ -1,27 +1,36 @@
-use std::sync::{Arc, Mutex};
+use std::{
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
 
-use anyhow::{Context, Ok, Result};
+use anyhow::{anyhow, Context, Ok, Result};
 use cpal::{
     traits::{DeviceTrait, HostTrait, StreamTrait},
-    BufferSize, Device, Host, SampleRate, StreamConfig,
+    BufferSize, Device, FromSample, Host, SampleFormat, SampleRate, SizedSample, Stream,
+    StreamConfig, SupportedBufferSize, SupportedStreamConfigRange,
 };
-use tokio::sync::{
-    mpsc::{channel, Receiver, Sender},
-    oneshot,
+use ringbuf::{HeapConsumer, HeapRb};
+use tokio::{
+    runtime::Handle,
+    sync::{
+        mpsc::{channel, Receiver, Sender},
+        oneshot,
+    },
 };
 
 use crate::{dirty_ui, BUFFER_SIZE};
 
 use super::{
-    audio_system::{InputSystem, OutputSystem, OutputSystemMessage},
+    audio_system::{InputSystem, OutputSystem},
     channel::{Channel, ChannelMessage},
-    BuffVec,
+    output_system, BuffVec,
 };
 
-pub type Float = f64;
-
 pub struct DirtyCore {
-    _host: Host,
+    host: Host,
     input_device: Device,
     output_device: Device,
 
@@ -30,7 +39,11 @@ pub struct DirtyCore {
 
     pub channels: Arc<Mutex<Vec<Channel>>>,
 
-    _core_rx: Receiver<DirtyCoreMessage>,
+    output_tx: Sender<output_system::OutputSystemMessage>,
+    output_consumer: Option<HeapConsumer<f32>>,
+    master_volume: Arc<AtomicU32>,
+
+    core_rx: Receiver<DirtyCoreMessage>,
     core_tx: Sender<DirtyCoreMessage>,
 }
 
@@ -52,14 +65,31 @@ impl DirtyCore {
 
         let (core_tx, core_rx) = channel::<DirtyCoreMessage>(1024);
 
+        // The master bus lives entirely inside `OutputSystem`'s own task; it
+        // hands finished blocks to the real-time output callback over the
+        // returned ring buffer consumer instead of a shared, lockable buffer.
+        let (output_system, output_consumer) = output_system::OutputSystem::new(
+            BUFFER_SIZE,
+            BUFFER_SIZE * 2 * 8,
+            output_config.sample_rate.0,
+            output_config.channels as usize,
+        );
+        let output_tx = output_system.start();
+
+        let mut channel = Channel::new(core_tx.clone());
+        channel.set_output_system(output_tx.clone());
+
         Ok(Self {
-            _host: host,
+            host,
             input_device,
             output_device,
             input_config,
             output_config,
-            channels: Arc::new(Mutex::new(vec![Channel::new(core_tx.clone())])),
-            _core_rx: core_rx,
+            channels: Arc::new(Mutex::new(vec![channel])),
+            output_tx,
+            output_consumer: Some(output_consumer),
+            master_volume: Arc::new(AtomicU32::new(1.0f32.to_bits())),
+            core_rx,
             core_tx,
         })
     }
@@ -68,22 +98,159 @@ impl DirtyCore {
         self.core_tx.clone()
     }
 
-    pub async fn run(&self, mut ui_rx: Receiver<dirty_ui::UIMessage>) -> Result<()> {
+    pub fn get_output_tx(&self) -> Sender<output_system::OutputSystemMessage> {
+        self.output_tx.clone()
+    }
+
+    pub fn list_input_devices(&self) -> Result<Vec<String>> {
+        Ok(self
+            .host
+            .input_devices()?
+            .filter_map(|device| device.name().ok())
+            .collect())
+    }
+
+    pub fn list_output_devices(&self) -> Result<Vec<String>> {
+        Ok(self
+            .host
+            .output_devices()?
+            .filter_map(|device| device.name().ok())
+            .collect())
+    }
+
+    pub fn set_input_device(&mut self, name: &str) -> Result<()> {
+        let device = self
+            .host
+            .input_devices()?
+            .find(|device| device.name().is_ok_and(|n| n == name))
+            .context("no such input device")?;
+        let default_config: StreamConfig = device.default_input_config()?.into();
+        let supported: Vec<_> = device.supported_input_configs()?.collect();
+        let config = resolve_stream_config(
+            default_config,
+            self.input_config.sample_rate,
+            self.input_config.buffer_size,
+            &supported,
+        );
+
+        self.input_device = device;
+        self.input_config = config;
+        Ok(())
+    }
+
+    /// Unlike the input side, swapping the output device means standing up a
+    /// whole new master bus: the ring buffer's consumer is a one-shot handoff
+    /// to the output callback (see `build_output_stream`), so once it's moved
+    /// into a stream it can't be reclaimed. A fresh `OutputSystem` is started
+    /// instead, and every channel's accumulator sender is repointed at it.
+    pub fn set_output_device(&mut self, name: &str) -> Result<()> {
+        let device = self
+            .host
+            .output_devices()?
+            .find(|device| device.name().is_ok_and(|n| n == name))
+            .context("no such output device")?;
+        let default_config: StreamConfig = device.default_output_config()?.into();
+        let supported: Vec<_> = device.supported_output_configs()?.collect();
+        let config = resolve_stream_config(
+            default_config,
+            self.output_config.sample_rate,
+            self.output_config.buffer_size,
+            &supported,
+        );
+
+        self.output_device = device;
+        self.output_config = config;
+        self.respin_output_system();
+        Ok(())
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.input_config.sample_rate = SampleRate(sample_rate);
+        self.output_config.sample_rate = SampleRate(sample_rate);
+    }
+
+    /// Stands up a fresh `OutputSystem` sized to the current `output_config`
+    /// and repoints every channel's accumulator sender at it. Needed
+    /// whenever the output side's ring buffer has to be rebuilt (a device
+    /// switch, or a standalone sample-rate change via `SetConfig`), since
+    /// the ring's consumer is a one-shot handoff into the currently running
+    /// output stream and can't be reclaimed once moved.
+    fn respin_output_system(&mut self) {
+        let (output_system, output_consumer) = output_system::OutputSystem::new(
+            BUFFER_SIZE,
+            BUFFER_SIZE * 2 * 8,
+            self.output_config.sample_rate.0,
+            self.output_config.channels as usize,
+        );
+        let output_tx = output_system.start();
+        for channel in self
+            .channels
+            .lock()
+            .expect("channels lock failed")
+            .iter_mut()
+        {
+            channel.set_output_system(output_tx.clone());
+        }
+
+        self.output_tx = output_tx;
+        self.output_consumer = Some(output_consumer);
+    }
+
+    /// Input side: the real-time callback only does a wait-free push into the
+    /// ring; a plain (non-real-time) task pops whole blocks and dispatches
+    /// them to the channels, so the `channels` mutex and the per-channel
+    /// processing never run on the audio thread. Returns the stream alongside
+    /// the drain task's handle so a device/config change can `abort()` the
+    /// old task before building a replacement pipeline.
+    ///
+    /// Dispatches on the device's native sample format so hosts that don't
+    /// expose `f32` (common on WASAPI/some ALSA configs) still work; samples
+    /// are converted to `f32`, the internal processing type, at the callback
+    /// boundary.
+    fn build_input_pipeline(&self) -> Result<(Stream, tokio::task::JoinHandle<()>)> {
+        match self.input_device.default_input_config()?.sample_format() {
+            SampleFormat::I16 => self.build_input_pipeline_typed::<i16>(),
+            SampleFormat::U16 => self.build_input_pipeline_typed::<u16>(),
+            SampleFormat::F32 => self.build_input_pipeline_typed::<f32>(),
+            format => Err(anyhow!("unsupported input sample format: {format:?}")),
+        }
+    }
+
+    fn build_input_pipeline_typed<T>(&self) -> Result<(Stream, tokio::task::JoinHandle<()>)>
+    where
+        T: SizedSample,
+        f32: FromSample<T>,
+    {
         let num_channels = self.input_config.channels as usize;
-        let output_buffers: Arc<Mutex<BuffVec<Float>>> =
-            Arc::new(Mutex::new(BuffVec::new(num_channels)));
-        let input_data_fn = move |data: &[Float], _: &cpal::InputCallbackInfo| {
-            let _input_buffers = Arc::new(BuffVec::deinterlace(data, num_channels));
+        let block_frames = num_channels * BUFFER_SIZE;
+
+        let (mut input_producer, mut input_consumer) = HeapRb::<f32>::new(block_frames * 8).split();
+        let mut convert_scratch: Vec<f32> = Vec::with_capacity(block_frames);
+        let input_data_fn = move |data: &[T], _: &cpal::InputCallbackInfo| {
+            convert_scratch.clear();
+            convert_scratch.extend(data.iter().map(|&sample| f32::from_sample(sample)));
+            input_producer.push_slice(&convert_scratch);
         };
 
         let channels = Arc::clone(&self.channels);
-        let output_data_fn = move |data: &mut [Float], _: &cpal::OutputCallbackInfo| {
-            let input: Vec<Float> = output_buffers.lock().unwrap().clone().collect();
-            let binding = channels.lock().expect("lock failed");
-            let channel = binding.first().expect("no channels");
-            let input: Vec<Float> = input.iter().map(|s| s * channel.volume).collect();
-            data.copy_from_slice(&input[..data.len()]);
-        };
+        let flush_tx = self.output_tx.clone();
+        let drain_task = tokio::spawn(async move {
+            let mut scratch = vec![0.0f32; block_frames];
+            loop {
+                if input_consumer.len() < block_frames {
+                    tokio::time::sleep(Duration::from_millis(1)).await;
+                    continue;
+                }
+                input_consumer.pop_slice(&mut scratch);
+                let buffer = Arc::new(BuffVec::deinterlace(&scratch, num_channels));
+                for channel in channels.lock().expect("channels lock failed").iter() {
+                    channel.process_audio(Arc::clone(&buffer)).await;
+                }
+                let _ = flush_tx
+                    .send(output_system::OutputSystemMessage::Flush)
+                    .await;
+            }
+        });
 
         let input_stream = InputSystem::get_input_stream(
             &self.input_device,
@@ -92,35 +259,207 @@ impl DirtyCore {
             err_fn,
             None,
         )?;
-        let output_stream = OutputSystem::get_output_stream(
+        Ok((input_stream, drain_task))
+    }
+
+    /// Output side: pop exactly `data.len()` samples with no locking, filling
+    /// silence on underrun, then apply master volume and hard-clamp every
+    /// sample to [-1.0, 1.0], so a hot mix is clipped at full scale rather
+    /// than overrunning the output format. Dispatches on the device's native
+    /// sample format the same way `build_input_pipeline` does.
+    fn build_output_stream(&mut self) -> Result<Stream> {
+        match self.output_device.default_output_config()?.sample_format() {
+            SampleFormat::I16 => self.build_output_stream_typed::<i16>(),
+            SampleFormat::U16 => self.build_output_stream_typed::<u16>(),
+            SampleFormat::F32 => self.build_output_stream_typed::<f32>(),
+            format => Err(anyhow!("unsupported output sample format: {format:?}")),
+        }
+    }
+
+    fn build_output_stream_typed<T>(&mut self) -> Result<Stream>
+    where
+        T: SizedSample + FromSample<f32>,
+    {
+        let mut output_consumer = self
+            .output_consumer
+            .take()
+            .context("output stream already running")?;
+        let master_volume = Arc::clone(&self.master_volume);
+        let mut scratch = vec![0.0f32; self.output_config.channels as usize * BUFFER_SIZE];
+        let output_data_fn = move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+            if scratch.len() < data.len() {
+                scratch.resize(data.len(), 0.0);
+            }
+            let scratch = &mut scratch[..data.len()];
+            let popped = output_consumer.pop_slice(scratch);
+            scratch[popped..].fill(0.0);
+
+            let master_volume = f32::from_bits(master_volume.load(Ordering::Relaxed));
+            for (sample, out) in scratch.iter().zip(data.iter_mut()) {
+                *out = T::from_sample((*sample * master_volume).clamp(-1.0, 1.0));
+            }
+        };
+
+        OutputSystem::get_output_stream(
             &self.output_device,
             &self.output_config,
             output_data_fn,
             err_fn,
             None,
-        )?;
+        )
+    }
 
+    pub async fn run(&mut self, mut ui_rx: Receiver<dirty_ui::UIMessage>) -> Result<()> {
+        let (mut input_stream, mut input_drain_task) = self.build_input_pipeline()?;
+        let mut output_stream = self.build_output_stream()?;
         input_stream.play()?;
         output_stream.play()?;
 
-        ui_rx.recv().await.context("ui tx dropped")?;
+        loop {
+            tokio::select! {
+                message = ui_rx.recv() => {
+                    message.context("ui tx dropped")?;
+                    break;
+                }
+                message = self.core_rx.recv() => {
+                    match message {
+                        Some(DirtyCoreMessage::GetOutputSystem(sender)) => {
+                            let _ = sender.send(self.output_tx.clone());
+                        }
+                        Some(DirtyCoreMessage::ListDevices(sender)) => {
+                            let devices = (
+                                self.list_input_devices().unwrap_or_default(),
+                                self.list_output_devices().unwrap_or_default(),
+                            );
+                            let _ = sender.send(devices);
+                        }
+                        Some(DirtyCoreMessage::SetInputDevice(name)) => {
+                            // Build (and start) the replacement pipeline before
+                            // tearing down the old one, so a device that turns
+                            // out not to build/play just logs and leaves the
+                            // previous input stream running instead of
+                            // propagating out of `run` and killing the engine.
+                            if self.set_input_device(&name).is_ok() {
+                                match self.build_input_pipeline().and_then(|(stream, task)| {
+                                    stream.play()?;
+                                    Ok((stream, task))
+                                }) {
+                                    std::result::Result::Ok((stream, task)) => {
+                                        input_drain_task.abort();
+                                        input_stream = stream;
+                                        input_drain_task = task;
+                                    }
+                                    Err(err) => {
+                                        eprintln!("failed to switch input device: {}", err);
+                                    }
+                                }
+                            }
+                        }
+                        Some(DirtyCoreMessage::SetOutputDevice(name)) => {
+                            if self.set_output_device(&name).is_ok() {
+                                match self.build_output_stream().and_then(|stream| {
+                                    stream.play()?;
+                                    Ok(stream)
+                                }) {
+                                    std::result::Result::Ok(stream) => {
+                                        output_stream = stream;
+                                    }
+                                    Err(err) => {
+                                        eprintln!("failed to switch output device: {}", err);
+                                    }
+                                }
+                            }
+                        }
+                        Some(DirtyCoreMessage::SetConfig(sample_rate)) => {
+                            // Unlike the device handlers, there's no
+                            // `self.set_config(...).is_ok()` gate to fail
+                            // before rebuilding: the new rate always applies,
+                            // so both pipelines always get rebuilt against it.
+                            self.set_sample_rate(sample_rate);
+                            self.respin_output_system();
+
+                            match self.build_input_pipeline().and_then(|(stream, task)| {
+                                stream.play()?;
+                                Ok((stream, task))
+                            }) {
+                                std::result::Result::Ok((stream, task)) => {
+                                    input_drain_task.abort();
+                                    input_stream = stream;
+                                    input_drain_task = task;
+                                }
+                                Err(err) => {
+                                    eprintln!(
+                                        "failed to rebuild input pipeline after config change: {}",
+                                        err
+                                    );
+                                }
+                            }
+                            match self.build_output_stream().and_then(|stream| {
+                                stream.play()?;
+                                Ok(stream)
+                            }) {
+                                std::result::Result::Ok(stream) => {
+                                    output_stream = stream;
+                                }
+                                Err(err) => {
+                                    eprintln!(
+                                        "failed to rebuild output pipeline after config change: {}",
+                                        err
+                                    );
+                                }
+                            }
+                        }
+                        Some(DirtyCoreMessage::StartRecording) => {
+                            let _ = self
+                                .output_tx
+                                .send(output_system::OutputSystemMessage::StartRecording)
+                                .await;
+                        }
+                        Some(DirtyCoreMessage::StopRecording(path)) => {
+                            let _ = self
+                                .output_tx
+                                .send(output_system::OutputSystemMessage::StopRecording(path))
+                                .await;
+                        }
+                        // Channel/buffer management isn't wired up yet.
+                        Some(DirtyCoreMessage::GetChannel(_, _))
+                        | Some(DirtyCoreMessage::NewChannel)
+                        | Some(DirtyCoreMessage::NewBuffer) => (),
+                        None => (),
+                    }
+                }
+            }
+        }
+
+        input_drain_task.abort();
         Ok(())
     }
 }
 
 pub enum DirtyCoreMessage {
-    GetOutputSystem(oneshot::Sender<Sender<OutputSystemMessage>>),
+    GetOutputSystem(oneshot::Sender<Sender<output_system::OutputSystemMessage>>),
     GetChannel(usize, oneshot::Sender<Result<Sender<ChannelMessage>>>),
 
     NewChannel,
 
     NewBuffer,
+
+    StartRecording,
+    StopRecording(std::path::PathBuf),
+
+    /// Reply is `(input_device_names, output_device_names)`.
+    ListDevices(oneshot::Sender<(Vec<String>, Vec<String>)>),
+    SetInputDevice(String),
+    SetOutputDevice(String),
+    SetConfig(u32),
 }
 
 #[derive(Clone, Copy)]
 pub enum AudioIO {
     None,
     Hardware(PhysicalAudioIO),
+    /// Routed from a looped, in-memory WAV file loaded via `Channel::load_file`.
+    File,
 }
 
 #[derive(Clone, Copy)]
@@ -129,6 +468,45 @@ pub enum PhysicalAudioIO {
     Stereo(usize, usize),
 }
 
+/// Keeps `desired_sample_rate`/`desired_buffer_size` only if `supported`
+/// actually advertises a matching-channel-count config covering them;
+/// otherwise falls back to `default_config`'s own values. Without this, a
+/// device switch could silently carry over a rate/buffer size the new
+/// device doesn't support, and `build_input_pipeline`/`build_output_stream`
+/// would fail outright the moment the stream is built.
+fn resolve_stream_config(
+    default_config: StreamConfig,
+    desired_sample_rate: SampleRate,
+    desired_buffer_size: BufferSize,
+    supported: &[SupportedStreamConfigRange],
+) -> StreamConfig {
+    let mut config = default_config;
+
+    let rate_supported = supported.iter().any(|range| {
+        range.channels() == config.channels
+            && range.min_sample_rate() <= desired_sample_rate
+            && desired_sample_rate <= range.max_sample_rate()
+    });
+    if rate_supported {
+        config.sample_rate = desired_sample_rate;
+    }
+
+    if let BufferSize::Fixed(frames) = desired_buffer_size {
+        let buffer_size_supported = supported.iter().any(|range| {
+            range.channels() == config.channels
+                && matches!(
+                    range.buffer_size(),
+                    SupportedBufferSize::Range { min, max } if frames >= *min && frames <= *max
+                )
+        });
+        if buffer_size_supported {
+            config.buffer_size = desired_buffer_size;
+        }
+    }
+
+    config
+}
+
 fn err_fn(err: cpal::StreamError) {
     eprintln!("an error occurred on stream: {}", err);
 }
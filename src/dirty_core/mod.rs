@@ -1,8 +1,10 @@
 pub use buffer::*;
 pub use channel::*;
 
+pub mod audio_system;
 pub mod buffer;
 pub mod channel;
 pub mod core;
-pub mod input_system;
 pub mod output_system;
+pub mod player;
+pub mod recorder;
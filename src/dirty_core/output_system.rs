@@ -1,10 +1,109 @@
-use tokio::sync::mpsc::{Receiver, Sender};
+use std::path::PathBuf;
+
+use ringbuf::{HeapConsumer, HeapProducer, HeapRb};
+use tokio::sync::mpsc::{channel, Receiver, Sender};
+
+use super::{recorder::Recorder, BuffVec};
 
 pub enum OutputSystemMessage {
-    Overdub(Vec<f32>),
+    Overdub(Vec<f32>, Vec<f32>),
+    /// Marks the end of one processing block: interleave whatever has been
+    /// overdubbed into the master bus, push it to the output ring, and reset
+    /// the bus for the next block.
+    Flush,
+
+    StartRecording,
+    StopRecording(PathBuf),
 }
 
+/// The master bus: every channel overdubs its processed stereo pair into this
+/// accumulator. Since `OutputSystem::run` is the accumulator's only owner,
+/// summing needs no locking; on `Flush` the finished block is handed to the
+/// real-time output callback over a lock-free ring buffer (see
+/// `DirtyCore::build_output_stream`),
+/// which only ever pops from it.
 pub struct OutputSystem {
-    _output_rx: Receiver<OutputSystemMessage>,
-    _output_tx: Sender<OutputSystemMessage>,
+    output_rx: Receiver<OutputSystemMessage>,
+    output_tx: Sender<OutputSystemMessage>,
+
+    master: BuffVec<f32>,
+    channels: usize,
+    block_size: usize,
+
+    ring_producer: HeapProducer<f32>,
+
+    sample_rate: u32,
+    recording: Option<Recorder>,
+}
+
+impl OutputSystem {
+    /// `channels` must match the output device's actual channel count: it
+    /// sizes the master bus, and `BuffVec`'s own iterator (relied on in
+    /// `Flush` below) interleaves frame-by-frame across exactly that many
+    /// channels before the block is handed to the output callback, which
+    /// pops `channels`-wide frames from the ring.
+    pub fn new(
+        block_size: usize,
+        ring_capacity: usize,
+        sample_rate: u32,
+        channels: usize,
+    ) -> (Self, HeapConsumer<f32>) {
+        let (output_tx, output_rx) = channel(128);
+        let (ring_producer, ring_consumer) = HeapRb::<f32>::new(ring_capacity).split();
+        (
+            Self {
+                output_rx,
+                output_tx,
+                master: BuffVec::new_zeroed(channels, block_size),
+                channels,
+                block_size,
+                ring_producer,
+                sample_rate,
+                recording: None,
+            },
+            ring_consumer,
+        )
+    }
+
+    pub fn start(self) -> Sender<OutputSystemMessage> {
+        let output_tx = self.output_tx.clone();
+        tokio::spawn(self.run());
+        output_tx
+    }
+
+    async fn run(mut self) {
+        while let Some(message) = self.output_rx.recv().await {
+            match message {
+                OutputSystemMessage::Overdub(left, right) => {
+                    let _ = self.master.overdub_channel(0, left);
+                    let _ = self.master.overdub_channel(1, right);
+                }
+                OutputSystemMessage::Flush => {
+                    let left = self.master.get_buffer(0).unwrap_or_default();
+                    let right = self.master.get_buffer(1).unwrap_or_default();
+                    if let Some(recorder) = self.recording.as_mut() {
+                        recorder.push_block(&left, &right);
+                    }
+                    // `BuffVec`'s `Iterator` impl already walks the buffer
+                    // frame-by-frame across every channel, so this stays
+                    // correctly interleaved no matter how many channels the
+                    // output device actually has.
+                    let interleaved: Vec<f32> = self.master.clone().collect();
+                    self.ring_producer.push_slice(&interleaved);
+                    self.master = BuffVec::new_zeroed(self.channels, self.block_size);
+                }
+
+                OutputSystemMessage::StartRecording => {
+                    self.recording = Some(Recorder::new(self.sample_rate));
+                }
+                OutputSystemMessage::StopRecording(path) => {
+                    if let Some(recorder) = self.recording.take() {
+                        if let Err(err) = recorder.write_wav(path) {
+                            eprintln!("failed to write wav file: {}", err);
+                        }
+                    }
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,69 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use hound::{SampleFormat, WavReader};
+
+/// A WAV file's samples, deinterleaved into stereo channels and read back a
+/// block at a time by `Channel::process_audio` via `AudioIO::File`. Mono
+/// files are played out of both channels, mirroring `PhysicalAudioIO::Mono`.
+pub struct LoadedFile {
+    left: Vec<f32>,
+    right: Vec<f32>,
+    position: usize,
+}
+
+impl LoadedFile {
+    pub fn load(path: PathBuf) -> Result<Self> {
+        let mut reader = WavReader::open(path).context("failed to open wav file")?;
+        let spec = reader.spec();
+        let samples: Vec<f32> = match spec.sample_format {
+            SampleFormat::Float => reader
+                .samples::<f32>()
+                .collect::<std::result::Result<_, _>>()
+                .context("failed to read wav samples")?,
+            SampleFormat::Int => {
+                // `hound` returns integer samples at their native bit depth,
+                // not widened to i32's full range, so normalize against that
+                // depth's own max magnitude rather than i32::MAX.
+                let full_scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+                reader
+                    .samples::<i32>()
+                    .map(|s| s.map(|s| s as f32 / full_scale))
+                    .collect::<std::result::Result<_, _>>()
+                    .context("failed to read wav samples")?
+            }
+        };
+
+        let (left, right) = if spec.channels <= 1 {
+            (samples.clone(), samples)
+        } else {
+            let channels = spec.channels as usize;
+            let left = samples.iter().step_by(channels).copied().collect();
+            let right = samples.iter().skip(1).step_by(channels).copied().collect();
+            (left, right)
+        };
+
+        Ok(Self {
+            left,
+            right,
+            position: 0,
+        })
+    }
+
+    /// Reads the next `len` samples per channel, looping back to the start
+    /// once the file runs out so it can be used for loop playback.
+    pub fn next_block(&mut self, len: usize) -> (Vec<f32>, Vec<f32>) {
+        if self.left.is_empty() {
+            return (vec![0.0; len], vec![0.0; len]);
+        }
+
+        let mut left = Vec::with_capacity(len);
+        let mut right = Vec::with_capacity(len);
+        for _ in 0..len {
+            left.push(self.left[self.position]);
+            right.push(self.right[self.position]);
+            self.position = (self.position + 1) % self.left.len();
+        }
+        (left, right)
+    }
+}
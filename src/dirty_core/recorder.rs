@@ -0,0 +1,43 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use hound::{SampleFormat, WavSpec, WavWriter};
+
+/// Accumulates the stereo blocks pushed from the master bus (see
+/// `OutputSystem::run`), interleaved into a growable in-memory buffer, then
+/// flushes them to a WAV file on `StopRecording`.
+pub struct Recorder {
+    sample_rate: u32,
+    samples: Vec<f32>,
+}
+
+impl Recorder {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            samples: Vec::new(),
+        }
+    }
+
+    pub fn push_block(&mut self, left: &[f32], right: &[f32]) {
+        self.samples
+            .extend(left.iter().zip(right).flat_map(|(l, r)| [*l, *r]));
+    }
+
+    pub fn write_wav(&self, path: PathBuf) -> Result<()> {
+        let spec = WavSpec {
+            channels: 2,
+            sample_rate: self.sample_rate,
+            bits_per_sample: 32,
+            sample_format: SampleFormat::Float,
+        };
+        let mut writer = WavWriter::create(path, spec).context("failed to create wav file")?;
+        for sample in &self.samples {
+            writer
+                .write_sample(*sample)
+                .context("failed to write wav sample")?;
+        }
+        writer.finalize().context("failed to finalize wav file")?;
+        Ok(())
+    }
+}
@@ -1,15 +1,26 @@
-use std::sync::{Arc, Mutex};
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
 
+use cpal::traits::{DeviceTrait, HostTrait};
 use eframe::{
-    egui::{CentralPanel, Context, Slider, Ui},
+    egui::{CentralPanel, ComboBox, Context, Slider, TextEdit, Ui},
     App,
 };
 use tokio::{
     runtime::Handle,
-    sync::mpsc::{channel, Receiver, Sender},
+    sync::{
+        mpsc::{channel, Receiver, Sender},
+        oneshot,
+    },
 };
 
-use crate::dirty_core::{channel::Channel, core::DirtyCore};
+use crate::dirty_core::{
+    channel::Channel,
+    core::{DirtyCore, DirtyCoreMessage},
+    output_system::OutputSystemMessage,
+};
 
 pub enum UIMessage {
     Quit,
@@ -18,15 +29,53 @@ pub enum UIMessage {
 pub struct DirtyUI {
     channels: Arc<Mutex<Vec<Channel>>>,
 
+    core_tx: Sender<DirtyCoreMessage>,
+    output_tx: Sender<OutputSystemMessage>,
+    recording: bool,
+    recording_path: String,
+    load_path: String,
+
+    input_devices: Vec<String>,
+    output_devices: Vec<String>,
+    selected_input_device: String,
+    selected_output_device: String,
+    sample_rate_input: String,
+
     ui_tx: Sender<UIMessage>,
 }
 
 impl DirtyUI {
     pub fn new(audio_sys: &DirtyCore) -> (Self, Receiver<UIMessage>) {
         let (ui_tx, ui_rx) = channel(16);
+
+        // Enumerated directly from the host rather than round-tripped through
+        // `DirtyCoreMessage::ListDevices`, since listing devices is a pure
+        // hardware query that doesn't need `DirtyCore`'s internal state.
+        let host = cpal::default_host();
+        let input_devices: Vec<String> = host
+            .input_devices()
+            .map(|devices| devices.filter_map(|d| d.name().ok()).collect())
+            .unwrap_or_default();
+        let output_devices: Vec<String> = host
+            .output_devices()
+            .map(|devices| devices.filter_map(|d| d.name().ok()).collect())
+            .unwrap_or_default();
+        let selected_input_device = input_devices.first().cloned().unwrap_or_default();
+        let selected_output_device = output_devices.first().cloned().unwrap_or_default();
+
         (
             Self {
                 channels: Arc::clone(&audio_sys.channels),
+                core_tx: audio_sys.get_tx(),
+                output_tx: audio_sys.get_output_tx(),
+                recording: false,
+                recording_path: "recording.wav".to_string(),
+                load_path: "loop.wav".to_string(),
+                input_devices,
+                output_devices,
+                selected_input_device,
+                selected_output_device,
+                sample_rate_input: "48000".to_string(),
                 ui_tx,
             },
             ui_rx,
@@ -63,6 +112,90 @@ impl App for DirtyUI {
             for channel in &mut *channels {
                 channel.draw_fader(ui);
             }
+            drop(channels);
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.add(TextEdit::singleline(&mut self.recording_path).desired_width(150.0));
+                let label = if self.recording { "stop" } else { "record" };
+                if ui.button(label).clicked() {
+                    let message = if self.recording {
+                        OutputSystemMessage::StopRecording(PathBuf::from(&self.recording_path))
+                    } else {
+                        OutputSystemMessage::StartRecording
+                    };
+                    self.recording = !self.recording;
+                    let _ = Handle::current().block_on(self.output_tx.send(message));
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.add(TextEdit::singleline(&mut self.load_path).desired_width(150.0));
+                if ui.button("load into channel 1").clicked() {
+                    let mut channels = self.channels.lock().expect("channels lock failed");
+                    if let Some(channel) = channels.first_mut() {
+                        if let Err(err) = channel.load_file(PathBuf::from(&self.load_path)) {
+                            eprintln!("failed to load file: {}", err);
+                        }
+                    }
+                }
+            });
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("refresh devices").clicked() {
+                    let (sender, receiver) = oneshot::channel();
+                    let _ = Handle::current()
+                        .block_on(self.core_tx.send(DirtyCoreMessage::ListDevices(sender)));
+                    if let Ok((inputs, outputs)) = Handle::current().block_on(receiver) {
+                        self.input_devices = inputs;
+                        self.output_devices = outputs;
+                    }
+                }
+                ui.add(TextEdit::singleline(&mut self.sample_rate_input).desired_width(80.0));
+                if ui.button("set sample rate").clicked() {
+                    if let Ok(sample_rate) = self.sample_rate_input.parse::<u32>() {
+                        let message = DirtyCoreMessage::SetConfig(sample_rate);
+                        let _ = Handle::current().block_on(self.core_tx.send(message));
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                ComboBox::from_label("input device")
+                    .selected_text(&self.selected_input_device)
+                    .show_ui(ui, |ui| {
+                        for device in &self.input_devices {
+                            ui.selectable_value(
+                                &mut self.selected_input_device,
+                                device.clone(),
+                                device,
+                            );
+                        }
+                    });
+                if ui.button("apply").clicked() {
+                    let message =
+                        DirtyCoreMessage::SetInputDevice(self.selected_input_device.clone());
+                    let _ = Handle::current().block_on(self.core_tx.send(message));
+                }
+            });
+            ui.horizontal(|ui| {
+                ComboBox::from_label("output device")
+                    .selected_text(&self.selected_output_device)
+                    .show_ui(ui, |ui| {
+                        for device in &self.output_devices {
+                            ui.selectable_value(
+                                &mut self.selected_output_device,
+                                device.clone(),
+                                device,
+                            );
+                        }
+                    });
+                if ui.button("apply").clicked() {
+                    let message =
+                        DirtyCoreMessage::SetOutputDevice(self.selected_output_device.clone());
+                    let _ = Handle::current().block_on(self.core_tx.send(message));
+                }
+            });
         });
     }
 
@@ -14,7 +14,13 @@ async fn main() -> Result<()> {
 
     let (ui, ui_rx) = DirtyUI::new(&core_sys);
 
-    let core_future = core_sys.run(ui_rx);
+    // `run_native` blocks the current thread until the window closes, so the
+    // core has to run on its own task to actually process audio (and UI
+    // messages) while the window is open, rather than only after it closes.
+    let core_task = tokio::spawn(async move {
+        let mut core_sys = core_sys;
+        core_sys.run(ui_rx).await
+    });
 
     let _ = run_native(
         "dirty",
@@ -22,7 +28,7 @@ async fn main() -> Result<()> {
         Box::new(|_cc| std::result::Result::Ok(Box::<DirtyUI>::new(ui))),
     );
 
-    core_future.await?;
+    core_task.await??;
 
     Ok(())
 }